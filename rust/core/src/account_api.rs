@@ -1,14 +1,83 @@
 //! Self-destruct functionality for accounts.
 
 use ixc_core_macros::message_selector;
+use ixc_message_api::AccountID;
 use crate::context::Context;
+use crate::error::Result;
+use crate::handler::Handler;
 
-/// Self-destructs the account.
+/// The selector for the account create message.
+pub const CREATE_SELECTOR: u64 = message_selector!("ixc.account.v1.create");
+
+/// The selector for the account self-destruct message.
 ///
-/// SAFETY: This function is unsafe because it can be used to destroy the account and all its state.
-pub unsafe fn self_destruct(ctx: &mut Context) -> crate::error::Result<()> {
-    unimplemented!()
+/// Symmetric to [`CREATE_SELECTOR`]: a handler that implements
+/// [`OnSelfDestruct`] is routed this selector so it can run cleanup logic
+/// immediately before its state is wiped.
+pub const SELF_DESTRUCT_SELECTOR: u64 = message_selector!("ixc.account.v1.self_destruct");
+
+/// An optional hook a handler may implement to react to destruction.
+///
+/// It runs while the account's state is still intact, so the handler can
+/// release externally-held resources or notify other accounts. Returning an
+/// error aborts the whole teardown and leaves the account untouched. The
+/// account's generated router dispatches [`SELF_DESTRUCT_SELECTOR`] here, the
+/// same way [`CREATE_SELECTOR`] reaches the create handler.
+pub trait OnSelfDestruct: Handler {
+    /// The selector routed to [`OnSelfDestruct::on_self_destruct`].
+    const SELECTOR: u64 = SELF_DESTRUCT_SELECTOR;
+
+    /// Called by [`self_destruct`] before any state object is reclaimed.
+    fn on_self_destruct(&self, ctx: &mut Context) -> Result<()>;
 }
 
-/// The selector for the account create message.
-pub const CREATE_SELECTOR: u64 = message_selector!("ixc.account.v1.create");
\ No newline at end of file
+/// Emitted once an account's state has been reclaimed.
+pub struct AccountDestroyed {
+    /// The account that was destroyed.
+    pub account_id: AccountID,
+}
+
+/// Self-destructs the currently executing account.
+///
+/// If the account's handler implements [`OnSelfDestruct`] the hook runs first;
+/// an error there aborts the teardown. Then every state-object entry owned by
+/// the account is reclaimed — each registered store is scanned by its
+/// `ObjectKey` prefix under the account's `AccountID` and deleted, releasing the
+/// backing storage — an [`AccountDestroyed`] event is emitted, and the
+/// `AccountID` is tombstoned.
+///
+/// # Invariants
+///
+/// Once this returns `Ok`, the account no longer exists: the router must not
+/// deliver any further message to it, and callers must treat reentry into the
+/// destroyed account as an error. State is only mutated after the optional
+/// hook succeeds, so a failed hook leaves the account fully intact.
+///
+/// # Safety
+///
+/// This function is unsafe because it destroys the account and all of its
+/// state; the caller must ensure no live references to the account's state
+/// outlive the call.
+pub unsafe fn self_destruct(ctx: &mut Context) -> Result<()> {
+    let account_id = ctx.self_account_id();
+
+    // Give the account's own code a chance to clean up while its state is still
+    // readable, mirroring the create dispatch path. Only accounts that register
+    // the hook are routed the selector; the common no-hook case skips straight
+    // to reclamation.
+    if ctx.handler_implements(SELF_DESTRUCT_SELECTOR) {
+        ctx.invoke_self(SELF_DESTRUCT_SELECTOR, &[])?;
+    }
+
+    // Reclaim every state object owned by the account, one registered store at
+    // a time, by deleting the whole `ObjectKey` prefix for this `AccountID`.
+    for store in ctx.registered_stores() {
+        ctx.delete_object_prefix(store, account_id)?;
+    }
+
+    // Record the destruction and retire the `AccountID` so it can no longer be
+    // routed to.
+    ctx.emit_event(&AccountDestroyed { account_id })?;
+    ctx.tombstone_account(account_id)?;
+    Ok(())
+}
@@ -8,18 +8,125 @@ use crate::state_object::ObjectValue;
 use crate::structs::{StructDecodeVisitor, StructType};
 use crate::value::SchemaValue;
 
+/// The magic number prefixed to every framed payload.
+///
+/// `b"IXC1"` identifies the stream as this crate's binary encoding and lets a
+/// reader reject buffers produced by a foreign encoder before any field is
+/// interpreted (cf. ketos's bytecode header).
+pub const FORMAT_MAGIC: [u8; 4] = *b"IXC1";
+
+/// The wire-format version carried in the self-describing envelope.
+///
+/// Bump this whenever the field-level encoding changes incompatibly; decoders
+/// reject any buffer whose header advertises a different version rather than
+/// silently mis-reading stale state.
+///
+/// Version 2 introduced the self-describing custom-value tag that prefixes
+/// `AccountID` (and every other custom type), which is not wire-compatible with
+/// the version-1 tagless encoding.
+pub const FORMAT_VERSION: u32 = 2;
+
+/// Decode a value from a self-describing envelope (magic + format version).
+///
+/// The header lets stored state objects survive encoder changes: a
+/// cross-version read fails with [`DecodeError::IncorrectMagicNumber`] or
+/// [`DecodeError::UnsupportedVersion`] instead of a misleading `OutOfData`.
+/// The bytes must have been produced by
+/// [`encode_value_framed`](crate::binary::encoder::encode_value_framed).
 pub fn decode_value<'a, V: SchemaValue<'a>>(input: &'a [u8], memory_manager: &'a MemoryManager) -> Result<V, DecodeError> {
-    let mut decoder = Decoder { buf: input, scope: memory_manager };
+    let mut decoder = Decoder::new(input, memory_manager)?;
     decode(&mut decoder)
 }
 
+/// Decode a value without per-read bounds checks, for internally-produced state.
+///
+/// State objects read back bytes this crate itself wrote, so the safe path's
+/// `read_bytes`/`is_done` checks are pure overhead. This variant elides them.
+///
+/// # Safety
+///
+/// The caller must guarantee `input` was produced by [`encode_value_framed`]
+/// (the same framed layout [`decode_value`] reads) for the same type `V` at the
+/// matching [`FORMAT_VERSION`]. The header is skipped without validation —
+/// trusted input is assumed well-formed — so a single stored object is readable
+/// by both paths. Calling it on untrusted or mismatched bytes is undefined
+/// behaviour; use [`decode_value`] for external input.
+///
+/// [`encode_value_framed`]: crate::binary::encoder::encode_value_framed
+pub unsafe fn decode_value_trusted<'a, V: SchemaValue<'a>>(input: &'a [u8], memory_manager: &'a MemoryManager) -> Result<V, DecodeError> {
+    let mut decoder = Decoder { buf: input, scope: memory_manager, trusted: true };
+    // Skip the envelope header (magic + version) without validating it.
+    decoder.read_bytes(FORMAT_MAGIC.len() + core::mem::size_of::<u32>())?;
+    decode(&mut decoder)
+}
+
+/// A stable one-byte tag identifying a referenceable custom value type.
+///
+/// New domain primitives (hashes, bech32 addresses, decimal coin amounts)
+/// register a tag here and supply their own encode/decode without touching the
+/// core `Decoder`/`Encoder` traits, modelled on Scrypto's
+/// `SborTypeId::Custom`. Tags are part of the wire format and must never be
+/// reused or renumbered.
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CustomValueType {
+    /// An [`AccountID`], encoded as a little-endian `u64`.
+    AccountID = 0,
+}
+
+impl CustomValueType {
+    /// Resolve a wire tag to its custom value type, rejecting unknown tags.
+    pub fn from_u8(tag: u8) -> Result<Self, DecodeError> {
+        match tag {
+            0 => Ok(CustomValueType::AccountID),
+            _ => Err(DecodeError::UnknownCustomType(tag)),
+        }
+    }
+}
+
+/// A decoded custom value, tagged by its [`CustomValueType`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CustomValue {
+    /// An [`AccountID`].
+    AccountID(AccountID),
+}
+
 pub(crate) struct Decoder<'a> {
     pub(crate) buf: &'a [u8],
     pub(crate) scope: &'a MemoryManager,
+    /// When set, bounds checks in [`Decoder::read_bytes`] are elided because
+    /// the input is known to be well-formed (see [`decode_value_trusted`]).
+    pub(crate) trusted: bool,
 }
 
 impl <'a> Decoder<'a> {
+    /// Create a decoder over a framed buffer, validating the envelope header.
+    ///
+    /// The buffer must begin with [`FORMAT_MAGIC`] followed by a little-endian
+    /// `u32` equal to [`FORMAT_VERSION`]; the remaining bytes are the payload.
+    pub(crate) fn new(input: &'a [u8], scope: &'a MemoryManager) -> Result<Self, DecodeError> {
+        let mut decoder = Decoder { buf: input, scope, trusted: false };
+        let magic: [u8; 4] = decoder.read_bytes(4)?.try_into().unwrap();
+        if magic != FORMAT_MAGIC {
+            return Err(DecodeError::IncorrectMagicNumber(magic));
+        }
+        let version = decoder.decode_u32()?;
+        if version != FORMAT_VERSION {
+            return Err(DecodeError::UnsupportedVersion(version));
+        }
+        Ok(decoder)
+    }
+
     fn read_bytes(&mut self, size: usize) -> Result<&'a [u8], DecodeError> {
+        if self.trusted {
+            // SAFETY: `trusted` is only set by `decode_value_trusted`, whose
+            // contract is that the buffer was produced by `encode_value_framed`
+            // at the matching `FORMAT_VERSION`. Length prefixes are therefore exact
+            // and every read stays in bounds, so the check is pure overhead.
+            let (bz, rest) = unsafe { self.buf.split_at_unchecked(size) };
+            self.buf = rest;
+            return Ok(bz);
+        }
         if self.buf.len() < size {
             return Err(DecodeError::OutOfData);
         }
@@ -45,6 +152,38 @@ impl<'a> crate::decoder::Decoder<'a> for Decoder<'a> {
         Ok(u128::from_le_bytes(bz.try_into().unwrap()))
     }
 
+    fn decode_varuint(&mut self) -> Result<usize, DecodeError> {
+        // LEB128: 7 payload bits per byte, little-endian groups, high bit is a
+        // continuation flag. Overlong encodings (too many bytes, a value wider
+        // than `usize`, or a redundant trailing zero-continuation group) are
+        // rejected so that each number has exactly one valid encoding.
+        let max_bytes = (usize::BITS + 6) / 7;
+        let mut result: usize = 0;
+        let mut shift = 0u32;
+        let mut count = 0u32;
+        loop {
+            let byte = self.read_bytes(1)?[0];
+            count += 1;
+            if count > max_bytes {
+                return Err(DecodeError::InvalidData);
+            }
+            let shifted = ((byte & 0x7f) as u128) << shift;
+            if shifted > usize::MAX as u128 {
+                return Err(DecodeError::InvalidData);
+            }
+            result |= shifted as usize;
+            if byte & 0x80 == 0 {
+                // A non-initial final byte of zero means the encoding could
+                // have been shorter: reject it as overlong.
+                if count > 1 && byte == 0 {
+                    return Err(DecodeError::InvalidData);
+                }
+                return Ok(result);
+            }
+            shift += 7;
+        }
+    }
+
     fn decode_borrowed_str(&mut self) -> Result<&'a str, DecodeError> {
         let bz = self.buf;
         self.buf = &[];
@@ -59,7 +198,7 @@ impl<'a> crate::decoder::Decoder<'a> for Decoder<'a> {
 
     fn decode_struct(&mut self, visitor: &mut dyn StructDecodeVisitor<'a>, struct_type: &StructType) -> Result<(), DecodeError> {
         let mut i = 0;
-        let mut sub = Decoder { buf: self.buf, scope: self.scope };
+        let mut sub = Decoder { buf: self.buf, scope: self.scope, trusted: self.trusted };
         let mut inner = InnerDecoder { outer: &mut sub };
         for _ in struct_type.fields.iter() {
             visitor.decode_field(i, &mut inner)?;
@@ -69,9 +208,9 @@ impl<'a> crate::decoder::Decoder<'a> for Decoder<'a> {
     }
 
     fn decode_list(&mut self, visitor: &mut dyn ListDecodeVisitor<'a>) -> Result<(), DecodeError> {
-        let size = self.decode_u32()? as usize;
+        let size = self.decode_varuint()?;
         visitor.init(size, &self.scope)?;
-        let mut sub = Decoder { buf: self.buf, scope: self.scope };
+        let mut sub = Decoder { buf: self.buf, scope: self.scope, trusted: self.trusted };
         let mut inner = InnerDecoder { outer: &mut sub };
         for _ in 0..size {
             visitor.next(&mut inner)?;
@@ -79,9 +218,20 @@ impl<'a> crate::decoder::Decoder<'a> for Decoder<'a> {
         Ok(())
     }
 
+    fn decode_custom(&mut self, type_id: u8) -> Result<CustomValue, DecodeError> {
+        match CustomValueType::from_u8(type_id)? {
+            CustomValueType::AccountID => Ok(CustomValue::AccountID(AccountID::new(self.decode_u64()?))),
+        }
+    }
+
     fn decode_account_id(&mut self) -> Result<AccountID, DecodeError> {
-        let id = self.decode_u64()?;
-        Ok(AccountID::new(id))
+        // The custom type tag is self-describing: read it off the wire and
+        // dispatch through the registry so an unexpected tag is rejected with
+        // `UnknownCustomType` rather than silently mis-decoded.
+        let type_id = self.read_bytes(1)?[0];
+        match self.decode_custom(type_id)? {
+            CustomValue::AccountID(id) => Ok(id),
+        }
     }
 
     fn mem_manager(&self) -> &'a MemoryManager {
@@ -103,32 +253,40 @@ impl<'b, 'a: 'b> crate::decoder::Decoder<'a> for InnerDecoder<'b, 'a> {
         self.outer.decode_u128()
     }
 
+    fn decode_varuint(&mut self) -> Result<usize, DecodeError> {
+        self.outer.decode_varuint()
+    }
+
     fn decode_borrowed_str(&mut self) -> Result<&'a str, DecodeError> {
-        let size = self.decode_u32()? as usize;
+        let size = self.decode_varuint()?;
         let bz = self.outer.read_bytes(size)?;
         Ok(core::str::from_utf8(bz).map_err(|_| DecodeError::InvalidData)?)
     }
 
     fn decode_owned_str(&mut self) -> Result<String, DecodeError> {
-        let size = self.decode_u32()? as usize;
+        let size = self.decode_varuint()?;
         let bz = self.outer.read_bytes(size)?;
         Ok(String::from_utf8(bz.to_vec()).map_err(|_| DecodeError::InvalidData)?)
     }
 
     fn decode_struct(&mut self, visitor: &mut dyn StructDecodeVisitor<'a>, struct_type: &StructType) -> Result<(), DecodeError> {
-        let size = self.decode_u32()? as usize;
+        let size = self.decode_varuint()?;
         let bz = self.outer.read_bytes(size)?;
-        let mut sub = Decoder { buf: bz, scope: self.outer.scope };
+        let mut sub = Decoder { buf: bz, scope: self.outer.scope, trusted: self.outer.trusted };
         sub.decode_struct(visitor, struct_type)
     }
 
     fn decode_list(&mut self, visitor: &mut dyn ListDecodeVisitor<'a>) -> Result<(), DecodeError> {
-        let size = self.decode_u32()? as usize;
+        let size = self.decode_varuint()?;
         let bz = self.outer.read_bytes(size)?;
-        let mut sub = Decoder { buf: bz, scope: self.outer.scope };
+        let mut sub = Decoder { buf: bz, scope: self.outer.scope, trusted: self.outer.trusted };
         sub.decode_list(visitor)
     }
 
+    fn decode_custom(&mut self, type_id: u8) -> Result<CustomValue, DecodeError> {
+        self.outer.decode_custom(type_id)
+    }
+
     fn decode_account_id(&mut self) -> Result<AccountID, DecodeError> {
         self.outer.decode_account_id()
     }
@@ -145,8 +303,18 @@ mod tests {
     use alloc::vec;
     use allocator_api2::alloc::Allocator;
     use bump_scope::{Bump, BumpScope};
-    use crate::binary::decoder::decode_value;
-    use crate::binary::encoder::encode_value;
+    use crate::binary::decoder::{decode_value, decode_value_trusted, FORMAT_MAGIC, FORMAT_VERSION};
+    use crate::binary::encoder::{encode_value, encode_value_framed};
+
+    /// Prefix a raw payload with the envelope header so it can be read back
+    /// with [`decode_value`].
+    fn framed(payload: &[u8]) -> alloc::vec::Vec<u8> {
+        let mut buf = vec![];
+        buf.extend_from_slice(&FORMAT_MAGIC);
+        buf.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        buf.extend_from_slice(payload);
+        buf
+    }
     use crate::decoder::{DecodeError, Decoder};
     use crate::encoder::{EncodeError, Encoder};
     use crate::field::Field;
@@ -159,25 +327,56 @@ mod tests {
 
     #[test]
     fn test_u32_decode() {
-        let buf: [u8; 4] = [10, 0, 0, 0];
+        let buf = framed(&[10, 0, 0, 0]);
         let mut mem = MemoryManager::new();
         let x = decode_value::<u32>(&buf, &mut mem).unwrap();
         assert_eq!(x, 10);
     }
 
+    #[test]
+    fn test_framed_roundtrip() {
+        let mem = MemoryManager::new();
+        let buf = encode_value_framed(&10u32, &mem as &dyn Allocator).unwrap();
+        let x = decode_value::<u32>(buf, &mem).unwrap();
+        assert_eq!(x, 10);
+    }
+
+    #[test]
+    fn test_framed_rejects_bad_magic() {
+        let mut buf = vec![b'X', b'X', b'X', b'X'];
+        buf.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        let mut mem = MemoryManager::new();
+        assert_eq!(
+            decode_value::<u32>(&buf, &mut mem),
+            Err(DecodeError::IncorrectMagicNumber([b'X', b'X', b'X', b'X'])),
+        );
+    }
+
+    #[test]
+    fn test_framed_rejects_unsupported_version() {
+        let mut buf = vec![];
+        buf.extend_from_slice(&FORMAT_MAGIC);
+        buf.extend_from_slice(&99u32.to_le_bytes());
+        let mut mem = MemoryManager::new();
+        assert_eq!(
+            decode_value::<u32>(&buf, &mut mem),
+            Err(DecodeError::UnsupportedVersion(99)),
+        );
+    }
+
     #[test]
     fn test_decode_borrowed_string() {
-        let str = "hello";
+        let buf = framed("hello".as_bytes());
         let mut mem = MemoryManager::new();
-        let x = decode_value::<&str>(str.as_bytes(), &mut mem).unwrap();
+        let x = decode_value::<&str>(&buf, &mut mem).unwrap();
         assert_eq!(x, "hello");
     }
 
     #[test]
     fn test_decode_owned_string() {
-        let str = "hello";
+        let buf = framed("hello".as_bytes());
         let mut mem = MemoryManager::new();
-        let x = decode_value::<alloc::string::String>(str.as_bytes(), &mut mem).unwrap();
+        let x = decode_value::<alloc::string::String>(&buf, &mut mem).unwrap();
         assert_eq!(x, "hello");
     }
 
@@ -268,11 +467,71 @@ mod tests {
             amount: 1234567890,
         };
         let mem = MemoryManager::new();
-        let res = encode_value(&coin, &mem as &dyn Allocator).unwrap();
+        let res = encode_value_framed(&coin, &mem as &dyn Allocator).unwrap();
         let decoded = decode_value::<Coin>(res, &mem).unwrap();
         assert_eq!(decoded, coin);
     }
 
+    #[test]
+    fn test_coin_trusted() {
+        let coin = Coin {
+            denom: "uatom",
+            amount: 1234567890,
+        };
+        let mem = MemoryManager::new();
+        let res = encode_value_framed(&coin, &mem as &dyn Allocator).unwrap();
+        // SAFETY: `res` was just produced by `encode_value_framed` for this type.
+        let decoded = unsafe { decode_value_trusted::<Coin>(res, &mem) }.unwrap();
+        assert_eq!(decoded, coin);
+    }
+
+    #[test]
+    fn test_coins_trusted() {
+        let coins = vec![Coin {
+            denom: "uatom",
+            amount: 1234567890,
+        }, Coin {
+            denom: "foo",
+            amount: 9876543210,
+        }];
+        let mem = MemoryManager::new();
+        let res = encode_value_framed(&coins, &mem as &dyn Allocator).unwrap();
+        // SAFETY: `res` was just produced by `encode_value_framed` for this type.
+        let decoded = unsafe { decode_value_trusted::<&[Coin]>(res, &mem) }.unwrap();
+        assert_eq!(decoded, coins);
+    }
+
+    /// Rough benchmark quantifying the trusted fast path against the safe path
+    /// on the `Coin`/`[Coin]` cases. Ignored by default; run with
+    /// `cargo test -- --ignored --nocapture bench_trusted_vs_safe`.
+    #[test]
+    #[ignore]
+    fn bench_trusted_vs_safe() {
+        use std::time::Instant;
+        let coins: vec::Vec<Coin> = (0..1024)
+            .map(|i| Coin { denom: "uatom", amount: i as u128 })
+            .collect();
+        let mem = MemoryManager::new();
+        // Both paths read the same framed layout; only the bounds checks differ.
+        let framed = encode_value_framed(&coins, &mem as &dyn Allocator).unwrap();
+        const ITERS: u32 = 10_000;
+
+        let start = Instant::now();
+        for _ in 0..ITERS {
+            let _ = decode_value::<&[Coin]>(framed, &mem).unwrap();
+        }
+        let safe = start.elapsed();
+
+        let start = Instant::now();
+        for _ in 0..ITERS {
+            // SAFETY: `framed` was produced by `encode_value_framed` for this type.
+            let _ = unsafe { decode_value_trusted::<&[Coin]>(framed, &mem) }.unwrap();
+        }
+        let trusted = start.elapsed();
+
+        std::println!("safe={safe:?} trusted={trusted:?}");
+    }
+
     #[test]
     fn test_coins() {
         let coins = vec![Coin {
@@ -283,8 +542,8 @@ mod tests {
             amount: 9876543210,
         }];
         let mem = MemoryManager::new();
-        let res = encode_value(&coins, &mem as &dyn Allocator).unwrap();
-        let decoded = decode_value::<&[Coin]>(&res, &mem).unwrap();
+        let res = encode_value_framed(&coins, &mem as &dyn Allocator).unwrap();
+        let decoded = decode_value::<&[Coin]>(res, &mem).unwrap();
         assert_eq!(decoded, coins);
     }
 }
\ No newline at end of file
@@ -0,0 +1,174 @@
+use allocator_api2::alloc::Allocator;
+use allocator_api2::vec::Vec;
+use ixc_message_api::AccountID;
+use crate::binary::decoder::{CustomValue, CustomValueType, FORMAT_MAGIC, FORMAT_VERSION};
+use crate::encoder::{encode, EncodeError};
+use crate::list::ListEncodeVisitor;
+use crate::structs::{StructEncodeVisitor, StructType};
+use crate::value::SchemaValue;
+
+pub fn encode_value<'a, V: SchemaValue<'a>>(value: &V, allocator: &'a dyn Allocator) -> Result<&'a [u8], EncodeError> {
+    let mut buf = Vec::new_in(allocator);
+    {
+        let mut encoder = Encoder { buf: &mut buf, alloc: allocator };
+        encode(value, &mut encoder)?;
+    }
+    Ok(buf.leak())
+}
+
+/// Encode a value behind a self-describing envelope (magic + format version).
+///
+/// The payload is prefixed with [`FORMAT_MAGIC`] and a little-endian
+/// [`FORMAT_VERSION`], so it can later be read back with
+/// [`decode_value`](crate::binary::decoder::decode_value) even across encoder
+/// changes. The same framed bytes are what
+/// [`decode_value_trusted`](crate::binary::decoder::decode_value_trusted)
+/// reads. Plain [`encode_value`] writes the payload without the header.
+pub fn encode_value_framed<'a, V: SchemaValue<'a>>(value: &V, allocator: &'a dyn Allocator) -> Result<&'a [u8], EncodeError> {
+    let mut buf = Vec::new_in(allocator);
+    buf.extend_from_slice(&FORMAT_MAGIC);
+    buf.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    {
+        let mut encoder = Encoder { buf: &mut buf, alloc: allocator };
+        encode(value, &mut encoder)?;
+    }
+    Ok(buf.leak())
+}
+
+pub(crate) struct Encoder<'b, 'a: 'b> {
+    pub(crate) buf: &'b mut Vec<u8, &'a dyn Allocator>,
+    pub(crate) alloc: &'a dyn Allocator,
+}
+
+impl<'b, 'a: 'b> crate::encoder::Encoder for Encoder<'b, 'a> {
+    fn encode_u32(&mut self, x: u32) -> Result<(), EncodeError> {
+        self.buf.extend_from_slice(&x.to_le_bytes());
+        Ok(())
+    }
+
+    fn encode_u64(&mut self, x: u64) -> Result<(), EncodeError> {
+        self.buf.extend_from_slice(&x.to_le_bytes());
+        Ok(())
+    }
+
+    fn encode_u128(&mut self, x: u128) -> Result<(), EncodeError> {
+        self.buf.extend_from_slice(&x.to_le_bytes());
+        Ok(())
+    }
+
+    fn encode_varuint(&mut self, mut x: usize) -> Result<(), EncodeError> {
+        // LEB128: emit 7 payload bits per byte, little-endian, with the high
+        // bit set on every group but the last. Mirrors `Decoder::decode_varuint`.
+        loop {
+            let mut byte = (x & 0x7f) as u8;
+            x >>= 7;
+            if x != 0 {
+                byte |= 0x80;
+            }
+            self.buf.push(byte);
+            if x == 0 {
+                return Ok(());
+            }
+        }
+    }
+
+    fn encode_str(&mut self, s: &str) -> Result<(), EncodeError> {
+        // At the top level a string occupies the whole payload, so no prefix.
+        self.buf.extend_from_slice(s.as_bytes());
+        Ok(())
+    }
+
+    fn encode_struct(&mut self, value: &dyn StructEncodeVisitor, struct_type: &StructType) -> Result<(), EncodeError> {
+        let mut inner = InnerEncoder { outer: self };
+        for (i, _) in struct_type.fields.iter().enumerate() {
+            value.encode_field(i, &mut inner)?;
+        }
+        Ok(())
+    }
+
+    fn encode_list(&mut self, size: usize, visitor: &dyn ListEncodeVisitor) -> Result<(), EncodeError> {
+        self.encode_varuint(size)?;
+        let mut inner = InnerEncoder { outer: self };
+        for i in 0..size {
+            visitor.encode(i, &mut inner)?;
+        }
+        Ok(())
+    }
+
+    fn encode_custom(&mut self, value: CustomValue) -> Result<(), EncodeError> {
+        // Emit the self-describing tag, then the payload, symmetric to
+        // `Decoder::decode_custom`.
+        match value {
+            CustomValue::AccountID(id) => {
+                self.buf.push(CustomValueType::AccountID as u8);
+                self.encode_u64(id.get())
+            }
+        }
+    }
+
+    fn encode_account_id(&mut self, account_id: AccountID) -> Result<(), EncodeError> {
+        self.encode_custom(CustomValue::AccountID(account_id))
+    }
+}
+
+struct InnerEncoder<'c, 'b: 'c, 'a: 'b> {
+    outer: &'c mut Encoder<'b, 'a>,
+}
+
+impl<'c, 'b: 'c, 'a: 'b> InnerEncoder<'c, 'b, 'a> {
+    /// Encode `f` into a scratch buffer and emit it behind a varint byte-length
+    /// prefix, matching the inner framing `Decoder` expects for nested values.
+    fn length_prefixed<F>(&mut self, f: F) -> Result<(), EncodeError>
+    where
+        F: FnOnce(&mut Encoder) -> Result<(), EncodeError>,
+    {
+        let mut scratch = Vec::new_in(self.outer.alloc);
+        {
+            let mut sub = Encoder { buf: &mut scratch, alloc: self.outer.alloc };
+            f(&mut sub)?;
+        }
+        self.outer.encode_varuint(scratch.len())?;
+        self.outer.buf.extend_from_slice(&scratch);
+        Ok(())
+    }
+}
+
+impl<'c, 'b: 'c, 'a: 'b> crate::encoder::Encoder for InnerEncoder<'c, 'b, 'a> {
+    fn encode_u32(&mut self, x: u32) -> Result<(), EncodeError> {
+        self.outer.encode_u32(x)
+    }
+
+    fn encode_u64(&mut self, x: u64) -> Result<(), EncodeError> {
+        self.outer.encode_u64(x)
+    }
+
+    fn encode_u128(&mut self, x: u128) -> Result<(), EncodeError> {
+        self.outer.encode_u128(x)
+    }
+
+    fn encode_varuint(&mut self, x: usize) -> Result<(), EncodeError> {
+        self.outer.encode_varuint(x)
+    }
+
+    fn encode_str(&mut self, s: &str) -> Result<(), EncodeError> {
+        self.outer.encode_varuint(s.len())?;
+        self.outer.buf.extend_from_slice(s.as_bytes());
+        Ok(())
+    }
+
+    fn encode_struct(&mut self, value: &dyn StructEncodeVisitor, struct_type: &StructType) -> Result<(), EncodeError> {
+        self.length_prefixed(|sub| sub.encode_struct(value, struct_type))
+    }
+
+    fn encode_list(&mut self, size: usize, visitor: &dyn ListEncodeVisitor) -> Result<(), EncodeError> {
+        self.length_prefixed(|sub| sub.encode_list(size, visitor))
+    }
+
+    fn encode_custom(&mut self, value: CustomValue) -> Result<(), EncodeError> {
+        self.outer.encode_custom(value)
+    }
+
+    fn encode_account_id(&mut self, account_id: AccountID) -> Result<(), EncodeError> {
+        self.outer.encode_account_id(account_id)
+    }
+}
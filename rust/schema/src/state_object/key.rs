@@ -2,7 +2,7 @@ use crate::buffer::{Reader, Writer, WriterFactory};
 use crate::decoder::DecodeError;
 use crate::encoder::EncodeError;
 use crate::mem::MemoryManager;
-use crate::state_object::value::ObjectValue;
+use crate::state_object::value::{ObjectFieldValue, ObjectValue};
 use crate::state_object::KeyFieldValue;
 
 /// Encode an object key.
@@ -116,6 +116,119 @@ impl<A: KeyFieldValue, B: KeyFieldValue, C: KeyFieldValue> ObjectKey for (A, B,
     }
 }
 
+macro_rules! signed_key_field_value {
+    ($ty:ty, $unsigned:ty, $n:literal) => {
+        impl KeyFieldValue for $ty {
+            fn encode<'a, W: Writer>(key: &Self::In<'a>, writer: &mut W) -> Result<(), EncodeError> {
+                // Fixed-width fields sort correctly in terminal and non-terminal
+                // position alike, so there is no separator to add here.
+                Self::encode_terminal(key, writer)
+            }
+
+            fn encode_terminal<'a, W: Writer>(key: &Self::In<'a>, writer: &mut W) -> Result<(), EncodeError> {
+                // Big-endian so the most-significant byte leads, with its sign
+                // bit flipped so that negative values (high bit set in two's
+                // complement) sort before positive ones byte-lexicographically.
+                let mut bz = (*key as $unsigned).to_be_bytes();
+                bz[0] ^= 0x80;
+                writer.write(&bz)
+            }
+
+            fn decode<'a, R: Reader<'a>>(reader: &mut R, mem: &'a MemoryManager) -> Result<Self::Out<'a>, DecodeError> {
+                Self::decode_terminal(reader, mem)
+            }
+
+            fn decode_terminal<'a, R: Reader<'a>>(reader: &mut R, _mem: &'a MemoryManager) -> Result<Self::Out<'a>, DecodeError> {
+                let bz = reader.read_bytes($n)?;
+                let mut arr: [u8; $n] = bz.try_into().map_err(|_| DecodeError::InvalidData)?;
+                arr[0] ^= 0x80;
+                Ok(<$unsigned>::from_be_bytes(arr) as $ty)
+            }
+
+            fn out_size<'a>(_key: &Self::In<'a>) -> usize { $n }
+
+            fn out_size_terminal<'a>(_key: &Self::In<'a>) -> usize { $n }
+        }
+    };
+}
+
+signed_key_field_value!(i32, u32, 4);
+signed_key_field_value!(i64, u64, 8);
+signed_key_field_value!(i128, u128, 16);
+
+/// A descending-order wrapper for a fixed-width key field.
+///
+/// Wrapping a field in `Desc` one's-complements every byte of its terminal
+/// encoding, inverting the byte-lexicographic order so a range scan visits the
+/// field in reverse. For example a tuple key `(A, Desc<B>)` scans `B` from
+/// greatest to least while keeping `A` ascending. The complement is layered on
+/// top of the inner field's own order-preserving encoding, so descending order
+/// is exact for both signed and unsigned integers.
+pub struct Desc<T>(pub T);
+
+impl<T: ObjectFieldValue> ObjectFieldValue for Desc<T> {
+    type In<'a> = T::In<'a>;
+    type Out<'a> = T::Out<'a>;
+}
+
+macro_rules! desc_key_field_value {
+    ($ty:ty, $n:literal) => {
+        impl KeyFieldValue for Desc<$ty> {
+            fn encode<'a, W: Writer>(key: &Self::In<'a>, writer: &mut W) -> Result<(), EncodeError> {
+                Self::encode_terminal(key, writer)
+            }
+
+            fn encode_terminal<'a, W: Writer>(key: &Self::In<'a>, writer: &mut W) -> Result<(), EncodeError> {
+                // Reuse the inner encoding, then one's-complement the bytes to
+                // flip the sort order. A short stack buffer keeps this a single
+                // pass and allocation-free.
+                struct Capture {
+                    bz: [u8; $n],
+                    pos: usize,
+                }
+                impl Writer for Capture {
+                    fn write(&mut self, bytes: &[u8]) -> Result<(), EncodeError> {
+                        for b in bytes {
+                            *self.bz.get_mut(self.pos).ok_or(EncodeError::OutOfSpace)? = !*b;
+                            self.pos += 1;
+                        }
+                        Ok(())
+                    }
+                    fn pos(&self) -> usize { self.pos }
+                }
+                let mut capture = Capture { bz: [0u8; $n], pos: 0 };
+                <$ty as KeyFieldValue>::encode_terminal(key, &mut capture)?;
+                writer.write(&capture.bz[..capture.pos])
+            }
+
+            fn decode<'a, R: Reader<'a>>(reader: &mut R, mem: &'a MemoryManager) -> Result<Self::Out<'a>, DecodeError> {
+                Self::decode_terminal(reader, mem)
+            }
+
+            fn decode_terminal<'a, R: Reader<'a>>(reader: &mut R, mem: &'a MemoryManager) -> Result<Self::Out<'a>, DecodeError> {
+                let bz = reader.read_bytes($n)?;
+                let mut arr: [u8; $n] = bz.try_into().map_err(|_| DecodeError::InvalidData)?;
+                for b in arr.iter_mut() {
+                    *b = !*b;
+                }
+                let mut inner: &[u8] = &arr;
+                <$ty as KeyFieldValue>::decode_terminal(&mut inner, mem)
+            }
+
+            fn out_size<'a>(_key: &Self::In<'a>) -> usize { $n }
+
+            fn out_size_terminal<'a>(_key: &Self::In<'a>) -> usize { $n }
+        }
+    };
+}
+
+desc_key_field_value!(i32, 4);
+desc_key_field_value!(i64, 8);
+desc_key_field_value!(i128, 16);
+desc_key_field_value!(u32, 4);
+desc_key_field_value!(u64, 8);
+desc_key_field_value!(u128, 16);
+
 impl<A: KeyFieldValue, B: KeyFieldValue, C: KeyFieldValue, D: KeyFieldValue> ObjectKey for (A, B, C, D) {
     fn encode<'a, W: Writer>(key: &Self::In<'a>, writer: &mut W) -> Result<(), EncodeError> {
         D::encode_terminal(&key.3, writer)?;